@@ -0,0 +1,339 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{App, AppId};
+
+/// Identifies the on-disk cache format.
+const MAGIC: &[u8; 4] = b"SCC1";
+
+/// How many records to append before compacting the cache file back
+/// down to one entry per id, bounding file growth from repeatedly
+/// re-recording the same id.
+const COMPACT_EVERY: usize = 200;
+
+#[derive(Debug, Clone)]
+pub enum CachedEntry {
+    /// A successfully crawled app, plus the outbound store-page links
+    /// discovered on it.
+    App { app: App, links: Vec<AppId> },
+    ShouldNotCrawl,
+}
+
+/// The on-disk payload for an `App` cache entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedApp {
+    app: App,
+    links: Vec<AppId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub fetched_at: SystemTime,
+    pub entry: CachedEntry,
+}
+
+/// A persisted, timestamped record of previously crawled (or rejected)
+/// app ids, keyed by [`AppId`].
+pub struct Cache {
+    path: PathBuf,
+    entries: HashMap<AppId, CacheEntry>,
+    /// Records appended since the last full rewrite of `path`.
+    pending_appends: usize,
+    /// Set when `path` exists but wasn't a readable cache of ours (e.g.
+    /// missing/mismatched magic), so the next write does a full rewrite
+    /// instead of appending onto whatever was there.
+    needs_rewrite: bool,
+}
+
+impl Cache {
+    /// Loads the cache from `path`. A missing or unrecognized file just
+    /// starts an empty cache.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut entries = HashMap::new();
+        let mut needs_rewrite = false;
+
+        if let Ok(file) = File::open(&path) {
+            let mut reader = BufReader::new(file);
+            let mut magic = [0u8; 4];
+            if reader.read_exact(&mut magic).is_ok() && &magic == MAGIC {
+                while let Some((id, entry)) = read_entry(&mut reader)? {
+                    entries.insert(id, entry);
+                }
+            } else {
+                needs_rewrite = true;
+            }
+        }
+
+        Ok(Self {
+            path,
+            entries,
+            pending_appends: 0,
+            needs_rewrite,
+        })
+    }
+
+    /// Returns the entry for `id` if one exists and is younger than
+    /// `max_age`.
+    pub fn get(&self, id: AppId, max_age: Duration) -> Option<&CacheEntry> {
+        self.entries.get(&id).filter(|entry| {
+            entry
+                .fetched_at
+                .elapsed()
+                .map(|age| age < max_age)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Records a successfully crawled app and its outbound links, and
+    /// persists the cache.
+    pub fn record_app(&mut self, app: App, links: Vec<AppId>) -> io::Result<()> {
+        let id = app.id;
+        let entry = CacheEntry {
+            fetched_at: SystemTime::now(),
+            entry: CachedEntry::App { app, links },
+        };
+        self.entries.insert(id, entry.clone());
+        self.append_or_compact(id, &entry)
+    }
+
+    /// Records that `id` should not be crawled again and persists the
+    /// cache.
+    pub fn record_should_not_crawl(&mut self, id: AppId) -> io::Result<()> {
+        let entry = CacheEntry {
+            fetched_at: SystemTime::now(),
+            entry: CachedEntry::ShouldNotCrawl,
+        };
+        self.entries.insert(id, entry.clone());
+        self.append_or_compact(id, &entry)
+    }
+
+    /// Persists one new record without rewriting the whole cache: the
+    /// common case is an append, with a full rewrite only every
+    /// [`COMPACT_EVERY`] records (or immediately, if `path` needs to be
+    /// brought back into a known-good state first).
+    fn append_or_compact(&mut self, id: AppId, entry: &CacheEntry) -> io::Result<()> {
+        if self.needs_rewrite {
+            return self.save();
+        }
+
+        self.pending_appends += 1;
+        if self.pending_appends >= COMPACT_EVERY {
+            return self.save();
+        }
+
+        let mut file = OpenOptions::new().append(true).create(true).open(&self.path)?;
+        if file.metadata()?.len() == 0 {
+            file.write_all(MAGIC)?;
+        }
+        write_entry(&mut file, id, entry)?;
+        file.flush()
+    }
+
+    /// Writes the cache to a temp file next to `self.path` and renames
+    /// it into place, so an interrupted write can't leave `self.path`
+    /// truncated. Also compacts the file back down to one record per
+    /// id, undoing however many appends piled up in between.
+    fn save(&mut self) -> io::Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(MAGIC)?;
+        for (id, entry) in &self.entries {
+            write_entry(&mut writer, *id, entry)?;
+        }
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        self.pending_appends = 0;
+        self.needs_rewrite = false;
+        Ok(())
+    }
+}
+
+fn write_entry(writer: &mut impl Write, id: AppId, entry: &CacheEntry) -> io::Result<()> {
+    writer.write_all(&id.to_be_bytes())?;
+
+    let millis = entry
+        .fetched_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    writer.write_all(&millis.to_be_bytes())?;
+
+    match &entry.entry {
+        CachedEntry::ShouldNotCrawl => {
+            writer.write_all(&[0u8])?;
+            writer.write_all(&0u32.to_be_bytes())?;
+        }
+        CachedEntry::App { app, links } => {
+            let payload = serde_json::to_vec(&CachedApp {
+                app: app.clone(),
+                links: links.clone(),
+            })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            writer.write_all(&[1u8])?;
+            writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+            writer.write_all(&payload)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_entry(reader: &mut impl Read) -> io::Result<Option<(AppId, CacheEntry)>> {
+    let mut id_buf = [0u8; 4];
+    match reader.read_exact(&mut id_buf) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let id = AppId::from_be_bytes(id_buf);
+
+    let mut millis_buf = [0u8; 8];
+    reader.read_exact(&mut millis_buf)?;
+    let fetched_at = UNIX_EPOCH + Duration::from_millis(u64::from_be_bytes(millis_buf));
+
+    let mut tag_buf = [0u8; 1];
+    reader.read_exact(&mut tag_buf)?;
+
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf)?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+
+    let entry = match tag_buf[0] {
+        0 => CachedEntry::ShouldNotCrawl,
+        1 => {
+            let mut payload = vec![0u8; len];
+            reader.read_exact(&mut payload)?;
+            let cached: CachedApp = serde_json::from_slice(&payload)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            CachedEntry::App {
+                app: cached.app,
+                links: cached.links,
+            }
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unknown cache entry tag",
+            ))
+        }
+    };
+
+    Ok(Some((id, CacheEntry { fetched_at, entry })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    #[test]
+    fn app_entry_round_trips_through_write_and_read() {
+        let app = App::new(
+            400,
+            "Portal".to_string(),
+            vec!["puzzle".to_string()],
+            999,
+            false,
+            "USD".to_string(),
+        );
+        let entry = CacheEntry {
+            fetched_at: SystemTime::now(),
+            entry: CachedEntry::App {
+                app: app.clone(),
+                links: vec![70, 220],
+            },
+        };
+
+        let mut buf = Vec::new();
+        write_entry(&mut buf, 400, &entry).unwrap();
+        let (id, read_back) = read_entry(&mut buf.as_slice()).unwrap().unwrap();
+
+        assert_eq!(id, 400);
+        match read_back.entry {
+            CachedEntry::App { app: read_app, links } => {
+                assert_eq!(read_app, app);
+                assert_eq!(links, vec![70, 220]);
+            }
+            CachedEntry::ShouldNotCrawl => panic!("expected App entry"),
+        }
+    }
+
+    #[test]
+    fn should_not_crawl_entry_round_trips_through_write_and_read() {
+        let entry = CacheEntry {
+            fetched_at: SystemTime::now(),
+            entry: CachedEntry::ShouldNotCrawl,
+        };
+
+        let mut buf = Vec::new();
+        write_entry(&mut buf, 7, &entry).unwrap();
+        let (id, read_back) = read_entry(&mut buf.as_slice()).unwrap().unwrap();
+
+        assert_eq!(id, 7);
+        assert!(matches!(read_back.entry, CachedEntry::ShouldNotCrawl));
+    }
+
+    fn temp_cache_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "steam_crawler_test_cache_{name}_{}.bin",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn record_app_appends_without_rewriting_and_reloads() {
+        let path = temp_cache_path("append");
+        fs::remove_file(&path).ok();
+
+        let mut cache = Cache::load(&path).unwrap();
+        cache
+            .record_app(
+                App::new(1, "A".to_string(), vec![], 0, true, "USD".to_string()),
+                vec![],
+            )
+            .unwrap();
+        cache
+            .record_app(
+                App::new(2, "B".to_string(), vec![], 0, true, "USD".to_string()),
+                vec![],
+            )
+            .unwrap();
+        assert_eq!(cache.pending_appends, 2);
+
+        let reloaded = Cache::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert!(reloaded.get(1, Duration::from_secs(60)).is_some());
+        assert!(reloaded.get(2, Duration::from_secs(60)).is_some());
+    }
+
+    #[test]
+    fn reaching_compact_every_rewrites_and_resets_pending_appends() {
+        let path = temp_cache_path("compact");
+        fs::remove_file(&path).ok();
+
+        let mut cache = Cache::load(&path).unwrap();
+        for i in 0..COMPACT_EVERY {
+            cache.record_should_not_crawl(i as AppId).unwrap();
+        }
+        fs::remove_file(&path).ok();
+
+        assert_eq!(cache.pending_appends, 0);
+    }
+}
@@ -1,38 +1,36 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
+    path::PathBuf,
     sync::{Arc, RwLock},
-    thread::JoinHandle,
     time::{Duration, Instant},
 };
 
 use clap::{Parser, ValueEnum};
 use scraper::{Html, Selector};
-use serde::Serializer;
-use serde_derive::Serialize;
+use serde::{Deserializer, Serializer};
+use serde_derive::{Deserialize, Serialize};
 use tracing::{info, warn};
 use url::Url;
 
-type AppId = u32;
+use crate::{cache::Cache, schedule::Scheduler, vdf::AppInfoEntry};
 
-fn page_for_app(id: AppId) -> String {
-    format!("https://store.steampowered.com/app/{id}/")
-}
+mod cache;
+mod price;
+mod report;
+mod schedule;
+mod storefront;
+mod vdf;
 
-fn parse_price(price: &str) -> f32 {
-    let price = price.to_lowercase();
-    if price.starts_with("free") || price.contains("play with firefly") || price.contains("demo") {
-        0.0
-    } else {
-        let new_price = price
-            .replace(',', ".")
-            .replace('-', "")
-            .chars()
-            .take_while(|c| *c != 'â‚¬')
-            .collect::<String>();
-        info!(new_price);
-        new_price.parse().unwrap_or(0.0)
-    }
+type AppId = u32;
+
+/// Builds the store page URL for `id`, requesting prices in the
+/// currency/language tied to `country` (a `cc` code like `"us"`).
+fn page_for_app(id: AppId, country: &str) -> String {
+    format!(
+        "https://store.steampowered.com/app/{id}/?cc={country}&l={}",
+        price::language_for_country(country)
+    )
 }
 
 #[derive(Parser)]
@@ -50,8 +48,29 @@ struct Options {
     #[arg(short, long)]
     format: Option<OutputFormat>,
     /// The space-separated list of seed IDs
-    #[arg(required = true)]
+    #[arg(required_unless_present("from_appinfo"))]
     seed: Vec<AppId>,
+    /// Seed (and enrich) the crawl from a local Steam `appinfo.vdf` cache
+    #[arg(long)]
+    from_appinfo: Option<PathBuf>,
+    /// Path to an on-disk crawl cache, reused across runs
+    #[arg(long)]
+    cache: Option<PathBuf>,
+    /// Maximum age, in seconds, of a cache entry before it's refetched
+    #[arg(long, default_value_t = 86400)]
+    max_age: u64,
+    /// The number of worker threads fetching pages concurrently
+    #[arg(long, default_value_t = 8)]
+    workers: usize,
+    /// The minimum delay, in milliseconds, between two requests
+    #[arg(long, default_value_t = 250)]
+    delay: u64,
+    /// Write a tag-trend and price-distribution summary to this path
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// The storefront country code (`cc`) to request prices in, e.g. `us`, `de`
+    #[arg(long, default_value = "us")]
+    country: String,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -65,13 +84,21 @@ enum TimeOrCount {
     Count(usize),
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct App {
     id: AppId,
     name: String,
-    #[serde(serialize_with = "flatten_tags")]
+    #[serde(
+        serialize_with = "flatten_tags",
+        deserialize_with = "unflatten_tags"
+    )]
     tags: Vec<String>,
-    price: f32,
+    /// Price in integer cents, so arithmetic and comparisons never hit
+    /// floating point rounding issues.
+    price: i64,
+    is_free: bool,
+    /// The ISO 4217 currency code `price` is denominated in.
+    currency: String,
 }
 
 impl Hash for App {
@@ -92,13 +119,34 @@ where
     serializer.serialize_str(&tags)
 }
 
+fn unflatten_tags<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let tags = <String as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(if tags.is_empty() {
+        Vec::new()
+    } else {
+        tags.split(',').map(str::to_string).collect()
+    })
+}
+
 impl App {
-    fn new(id: AppId, name: String, tags: Vec<String>, price: f32) -> Self {
+    fn new(
+        id: AppId,
+        name: String,
+        tags: Vec<String>,
+        price: i64,
+        is_free: bool,
+        currency: String,
+    ) -> Self {
         Self {
             id,
             name,
             tags,
             price,
+            is_free,
+            currency,
         }
     }
 }
@@ -108,12 +156,32 @@ struct Crawler {
     ids: Arc<RwLock<VecDeque<AppId>>>,
     should_not_crawl: Arc<RwLock<Vec<AppId>>>,
     apps: Arc<RwLock<HashSet<App>>>,
-    threads: VecDeque<JoinHandle<color_eyre::Result<()>>>,
+    cache: Option<Arc<RwLock<Cache>>>,
+    max_age: Duration,
+    workers: usize,
+    scheduler: Arc<Scheduler>,
+    local_library: Arc<HashMap<AppId, AppInfoEntry>>,
+    country: Arc<String>,
 }
 
 impl Crawler {
-    fn new() -> Self {
-        Default::default()
+    fn new(
+        cache: Option<Cache>,
+        max_age: Duration,
+        workers: usize,
+        delay: Duration,
+        local_library: HashMap<AppId, AppInfoEntry>,
+        country: String,
+    ) -> Self {
+        Self {
+            cache: cache.map(|cache| Arc::new(RwLock::new(cache))),
+            max_age,
+            workers,
+            scheduler: Arc::new(Scheduler::new(delay)),
+            local_library: Arc::new(local_library),
+            country: Arc::new(country),
+            ..Default::default()
+        }
     }
 
     fn apps(&self) -> Vec<App> {
@@ -126,72 +194,162 @@ impl Crawler {
         }
         let started_at = Instant::now();
 
+        let handles: Vec<_> = (0..self.workers.max(1))
+            .map(|_| {
+                let scheduler = self.scheduler.clone();
+                let ids = self.ids.clone();
+                let should_not_crawl = self.should_not_crawl.clone();
+                let apps = self.apps.clone();
+                let cache = self.cache.clone();
+                let local_library = self.local_library.clone();
+                let country = self.country.clone();
+                std::thread::spawn(move || {
+                    worker_loop(
+                        scheduler,
+                        ids,
+                        should_not_crawl,
+                        apps,
+                        cache,
+                        local_library,
+                        country,
+                    )
+                })
+            })
+            .collect();
+
         loop {
-            let id = self.ids.write().unwrap().pop_front();
-            if let Some(id) = id {
-                match time_or_count {
-                    TimeOrCount::Time(time) => {
-                        let app_known = self.apps.read().unwrap().iter().any(|app| app.id == id);
-                        let should_not_crawl = self.should_not_crawl.read().unwrap().contains(&id);
-                        if started_at.elapsed() < time && !app_known && !should_not_crawl {
-                            let ids = self.ids.clone();
-                            let should_not_crawl = self.should_not_crawl.clone();
-                            let apps = self.apps.clone();
-                            self.threads.push_back(std::thread::spawn(move || {
-                                crawl_id(id, ids, should_not_crawl, apps)
-                            }));
+            let finished = match time_or_count {
+                TimeOrCount::Time(time) => started_at.elapsed() >= time,
+                TimeOrCount::Count(count) => self.apps.read().unwrap().len() >= count,
+            };
+            if finished {
+                break;
+            }
+
+            let Some(id) = self.ids.write().unwrap().pop_front() else {
+                info!("{} entries", self.apps.read().unwrap().len());
+                std::thread::sleep(Duration::from_millis(100));
+                continue;
+            };
+
+            // Skip ids we've already resolved one way or the other
+            // before even looking at the cache, so a cached app whose
+            // links point back into an already-known cluster doesn't
+            // keep re-extending `self.ids` with the same ids forever.
+            let app_known = self.apps.read().unwrap().iter().any(|app| app.id == id);
+            let should_not_crawl = self.should_not_crawl.read().unwrap().contains(&id);
+            if app_known || should_not_crawl {
+                continue;
+            }
+
+            if let Some(cache) = &self.cache {
+                let cached = cache.read().unwrap().get(id, self.max_age).cloned();
+                if let Some(cached) = cached {
+                    match cached.entry {
+                        cache::CachedEntry::App { app, links } => {
+                            self.apps.write().unwrap().insert(app);
+                            self.ids.write().unwrap().extend(links);
                         }
-                    }
-                    TimeOrCount::Count(count) => {
-                        let len = self.apps.read().unwrap().len();
-                        let app_known = self.apps.read().unwrap().iter().any(|app| app.id == id);
-                        let should_not_crawl = self.should_not_crawl.read().unwrap().contains(&id);
-                        if len < count && !app_known && !should_not_crawl {
-                            let ids = self.ids.clone();
-                            let should_not_crawl = self.should_not_crawl.clone();
-                            let apps = self.apps.clone();
-                            self.threads.push_back(std::thread::spawn(move || {
-                                crawl_id(id, ids, should_not_crawl, apps)
-                            }));
+                        cache::CachedEntry::ShouldNotCrawl => {
+                            self.should_not_crawl.write().unwrap().push(id);
                         }
                     }
-                }
-            } else {
-                let len = self.apps.read().unwrap().len();
-                if let TimeOrCount::Count(count) = time_or_count {
-                    if len < count {
-                        info!("{len} entries");
-                        std::thread::sleep(Duration::from_millis(100));
-                        continue;
-                    } else {
-                        break;
-                    }
+                    continue;
                 }
             }
+
+            self.scheduler.push(id);
         }
 
-        while let Some(thread) = self.threads.pop_front() {
-            thread.join().unwrap()?;
+        self.scheduler.close();
+        for handle in handles {
+            handle.join().unwrap();
         }
 
         Ok(())
     }
 }
 
+/// Pulls ids off the scheduler as the rate limit allows and crawls them,
+/// re-enqueueing ones that hit a retryable error instead of dropping
+/// them.
+fn worker_loop(
+    scheduler: Arc<Scheduler>,
+    ids: Arc<RwLock<VecDeque<AppId>>>,
+    should_not_crawl: Arc<RwLock<Vec<AppId>>>,
+    apps: Arc<RwLock<HashSet<App>>>,
+    cache: Option<Arc<RwLock<Cache>>>,
+    local_library: Arc<HashMap<AppId, AppInfoEntry>>,
+    country: Arc<String>,
+) {
+    while let Some((id, attempt)) = scheduler.pop() {
+        match crawl_id(
+            id,
+            ids.clone(),
+            should_not_crawl.clone(),
+            apps.clone(),
+            cache.clone(),
+            local_library.clone(),
+            &country,
+            &scheduler,
+        ) {
+            Ok(CrawlOutcome::Done) => {}
+            Ok(CrawlOutcome::Retry) => {
+                if scheduler.retry(id, attempt) {
+                    warn!("Rate limited while crawling {id}, backing off and retrying");
+                } else {
+                    warn!("Giving up on {id} after repeated rate limiting/server errors");
+                }
+            }
+            Err(e) => {
+                warn!("Failed to crawl {id}: {e:?}");
+            }
+        }
+    }
+}
+
+/// What happened while crawling one id: either it's fully handled, or it
+/// hit a transient error (HTTP 429/5xx) and should be retried later.
+enum CrawlOutcome {
+    Done,
+    Retry,
+}
+
+/// Whether `err` wraps a `ureq` status error worth retrying rather than
+/// giving up on, i.e. a rate limit or a server-side failure.
+fn is_retryable(err: &color_eyre::Report) -> bool {
+    err.downcast_ref::<ureq::Error>()
+        .map(|e| matches!(e, ureq::Error::Status(code, _) if *code == 429 || *code >= 500))
+        .unwrap_or(false)
+}
+
 fn crawl_id(
     id: AppId,
     ids: Arc<RwLock<VecDeque<AppId>>>,
     should_not_crawl: Arc<RwLock<Vec<AppId>>>,
     apps: Arc<RwLock<HashSet<App>>>,
-) -> color_eyre::Result<()> {
+    cache: Option<Arc<RwLock<Cache>>>,
+    local_library: Arc<HashMap<AppId, AppInfoEntry>>,
+    country: &str,
+    scheduler: &Scheduler,
+) -> color_eyre::Result<CrawlOutcome> {
     info!("Crawling id {id}");
-    let page = ureq::get(&page_for_app(id))
+    let page = match ureq::get(&page_for_app(id, country))
         .set(
             "Cookie",
             "wants_mature_content=1; birthtime=1101855601; lastagecheckage=1-0-2000",
         )
-        .call()?
-        .into_string()?;
+        .call()
+    {
+        Ok(response) => response.into_string()?,
+        Err(e) => {
+            let err = color_eyre::Report::from(e);
+            if is_retryable(&err) {
+                return Ok(CrawlOutcome::Retry);
+            }
+            return Err(err);
+        }
+    };
     let document = Html::parse_document(&page);
 
     let link_selector = Selector::parse("a").unwrap();
@@ -212,11 +370,73 @@ fn crawl_id(
                 .unwrap()
         })
         .collect();
+    let links: Vec<AppId> = links.into_iter().collect();
 
     ids.write()
         .unwrap()
-        .append(&mut links.into_iter().collect::<VecDeque<_>>());
+        .append(&mut links.iter().copied().collect::<VecDeque<_>>());
+
+    // `appdetails` is a second outgoing request for this id, so reserve
+    // its own rate-limit slot rather than firing it unthrottled right
+    // after the page fetch above.
+    scheduler.throttle();
+
+    let app = match storefront::fetch_appdetails(id, country) {
+        Ok(Some(app)) => Some(app),
+        Ok(None) => {
+            info!("No usable appdetails entry for {id}, falling back to HTML scrape");
+            scrape_app(id, &document)
+        }
+        Err(e) if is_retryable(&e) => return Ok(CrawlOutcome::Retry),
+        Err(e) => {
+            warn!("appdetails request for {id} failed: {e:?}, falling back to HTML scrape");
+            scrape_app(id, &document)
+        }
+    };
+
+    match app {
+        Some(mut app) => {
+            enrich_with_local_library(&mut app, &local_library);
+            if let Some(cache) = &cache {
+                cache.write().unwrap().record_app(app.clone(), links.clone())?;
+            }
+            apps.write().unwrap().insert(app);
+        }
+        None => {
+            info!("Skipping invalid app {id}");
+            if let Some(cache) = &cache {
+                cache.write().unwrap().record_should_not_crawl(id)?;
+            }
+            should_not_crawl.write().unwrap().push(id);
+        }
+    }
+    Ok(CrawlOutcome::Done)
+}
+
+/// Fills in `app`'s name and tags from the local `appinfo.vdf` library,
+/// if an entry exists for it, so locally-known metadata doesn't get
+/// dropped just because the storefront omitted it.
+fn enrich_with_local_library(app: &mut App, local_library: &HashMap<AppId, AppInfoEntry>) {
+    let Some(entry) = local_library.get(&app.id) else {
+        return;
+    };
 
+    if app.name.is_empty() {
+        if let Some(name) = &entry.name {
+            app.name = name.clone();
+        }
+    }
+
+    for tag in &entry.tags {
+        if !app.tags.contains(tag) {
+            app.tags.push(tag.clone());
+        }
+    }
+}
+
+/// Extracts an [`App`] from a store page's HTML, as used when the
+/// `appdetails` API doesn't have a usable entry for the id.
+fn scrape_app(id: AppId, document: &Html) -> Option<App> {
     let tag_selector = Selector::parse(".app_tag").unwrap();
     let tags: Vec<_> = document
         .select(&tag_selector)
@@ -225,41 +445,31 @@ fn crawl_id(
         .collect();
     let price_selector = Selector::parse(".price").unwrap();
     let purchase_selector = Selector::parse(".game_purchase_action").unwrap();
-    let price = document
+    let (price, currency) = document
         .select(&purchase_selector)
         .map(|action| {
             if let Some(id) = action.value().id() {
                 if id == "dlc_purchase_action" {
-                    return 0.0;
+                    return (0, price::DEFAULT_CURRENCY.to_string());
                 }
             }
 
             match action.select(&price_selector).next() {
-                Some(price_element) => parse_price(price_element.inner_html().trim()),
-                None => 0.0,
+                Some(price_element) => price::parse_price(price_element.inner_html().trim()),
+                None => (0, price::DEFAULT_CURRENCY.to_string()),
             }
         })
-        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        .max_by_key(|(cents, _)| *cents)?;
 
-    if price.is_none() {
-        info!("Skipping invalid app {id}");
-        should_not_crawl.write().unwrap().push(id);
-        return Ok(());
-    }
-
-    let price = price.unwrap();
     let name_selector = Selector::parse(".apphub_AppName").unwrap();
     let name = document
         .select(&name_selector)
-        .next()
-        .unwrap()
+        .next()?
         .inner_html()
         .trim()
         .to_string();
 
-    let app = App::new(id, name, tags, price);
-    apps.write().unwrap().insert(app);
-    Ok(())
+    Some(App::new(id, name, tags, price, price == 0, currency))
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -277,26 +487,50 @@ fn main() -> color_eyre::Result<()> {
             .finish(),
     )?;
 
-    let mut crawler = Crawler::new();
+    let mut seed = opts.seed.clone();
+    let mut local_library = HashMap::new();
+    if let Some(path) = &opts.from_appinfo {
+        for entry in vdf::parse_appinfo(path)? {
+            seed.push(entry.app_id);
+            local_library.insert(entry.app_id, entry);
+        }
+    }
 
-    if let Err(e) = crawler.crawl(&opts.seed, time_or_count) {
+    let cache = opts.cache.clone().map(Cache::load).transpose()?;
+    let mut crawler = Crawler::new(
+        cache,
+        Duration::from_secs(opts.max_age),
+        opts.workers,
+        Duration::from_millis(opts.delay),
+        local_library,
+        opts.country.clone(),
+    );
+
+    if let Err(e) = crawler.crawl(&seed, time_or_count) {
         warn!("An error occured during crawling: {e:?}. Printing possibly invalid data.")
     }
 
+    let apps = crawler.apps();
+
     match opts.format {
         Some(OutputFormat::Json) => {
-            let apps = serde_json::to_string(&crawler.apps())?;
+            let apps = serde_json::to_string(&apps)?;
             println!("{apps}")
         }
         _ => {
-            let mut apps = csv::WriterBuilder::default()
+            let mut writer = csv::WriterBuilder::default()
                 .delimiter(b';')
                 .from_writer(std::io::stdout());
-            for app in crawler.apps() {
-                apps.serialize(app)?;
+            for app in &apps {
+                writer.serialize(app)?;
             }
         }
     }
 
+    if let Some(path) = &opts.report {
+        let report = report::build_report(&apps);
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+
     Ok(())
 }
@@ -0,0 +1,94 @@
+/// Currency symbols/codes recognized in store price strings, along with
+/// whether that locale writes the decimal separator as `,` (and `.` as
+/// the thousands separator) rather than the other way round.
+const CURRENCIES: &[(&str, &str, bool)] = &[
+    ("$", "USD", false),
+    ("£", "GBP", false),
+    ("€", "EUR", true),
+    ("¥", "JPY", false),
+    ("₩", "KRW", false),
+    ("₽", "RUB", true),
+    ("kr", "SEK", true),
+];
+
+/// The fallback currency for price strings with no recognizable
+/// symbol/code (e.g. "Free").
+pub(crate) const DEFAULT_CURRENCY: &str = "USD";
+
+/// Parses a store price string into a normalized amount (in hundredths
+/// of the currency's main unit, e.g. cents, for every currency including
+/// JPY and KRW) and its detected ISO 4217 currency code.
+pub fn parse_price(price: &str) -> (i64, String) {
+    let price = price.trim().to_lowercase();
+    if price.starts_with("free") || price.contains("play with firefly") || price.contains("demo") {
+        return (0, DEFAULT_CURRENCY.to_string());
+    }
+
+    let (currency, comma_is_decimal, amount) = detect_currency(&price);
+    (parse_amount(&amount, comma_is_decimal), currency)
+}
+
+fn detect_currency(price: &str) -> (String, bool, String) {
+    for (symbol, code, comma_is_decimal) in CURRENCIES {
+        if price.contains(symbol) {
+            return (code.to_string(), *comma_is_decimal, price.replace(symbol, ""));
+        }
+    }
+    (DEFAULT_CURRENCY.to_string(), false, price.to_string())
+}
+
+fn parse_amount(amount: &str, comma_is_decimal: bool) -> i64 {
+    let amount = amount.replace('-', "");
+    let normalized = if comma_is_decimal {
+        amount.replace('.', "").replace(',', ".")
+    } else {
+        amount.replace(',', "")
+    };
+    let value: f64 = normalized.trim().parse().unwrap_or(0.0);
+    (value * 100.0).round() as i64
+}
+
+/// Steam's storefront `l` (language) query parameter for a given `cc`
+/// (country) code. Unlisted countries default to English.
+pub fn language_for_country(country: &str) -> &'static str {
+    match country.to_lowercase().as_str() {
+        "de" | "at" | "ch" => "german",
+        "fr" => "french",
+        "jp" => "japanese",
+        "kr" => "koreana",
+        "ru" => "russian",
+        "cn" => "schinese",
+        "br" => "brazilian",
+        _ => "english",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_usd_with_period_decimal() {
+        assert_eq!(parse_price("$19.99"), (1999, "USD".to_string()));
+    }
+
+    #[test]
+    fn parses_eur_with_comma_decimal() {
+        assert_eq!(parse_price("19,99€"), (1999, "EUR".to_string()));
+    }
+
+    #[test]
+    fn parses_gbp_with_thousands_separator() {
+        assert_eq!(parse_price("£1,234.50"), (123450, "GBP".to_string()));
+    }
+
+    #[test]
+    fn parses_free_as_zero() {
+        assert_eq!(parse_price("Free"), (0, DEFAULT_CURRENCY.to_string()));
+    }
+
+    #[test]
+    fn unrecognized_currency_falls_back_to_default() {
+        assert_eq!(parse_price("9.99"), (999, DEFAULT_CURRENCY.to_string()));
+    }
+}
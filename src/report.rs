@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::App;
+
+/// How many apps are tagged with a given tag, as well as the price
+/// distribution across them.
+#[derive(Debug, Serialize)]
+pub struct TagStats {
+    pub tag: String,
+    pub count: usize,
+    pub mean_price: f64,
+    pub median_price: f64,
+}
+
+/// How often two tags co-occur on the same app.
+#[derive(Debug, Serialize)]
+pub struct TagPairCount {
+    pub a: String,
+    pub b: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub tags: Vec<TagStats>,
+    pub top_tag_pairs: Vec<TagPairCount>,
+}
+
+const TOP_TAG_PAIRS: usize = 20;
+
+/// Aggregates per-tag counts and price statistics, plus the
+/// most-co-occurring tag pairs, over a crawl's apps.
+pub fn build_report(apps: &[App]) -> Report {
+    let mut prices_by_tag: HashMap<String, Vec<i64>> = HashMap::new();
+    for app in apps {
+        for tag in &app.tags {
+            prices_by_tag
+                .entry(tag.clone())
+                .or_default()
+                .push(app.price);
+        }
+    }
+
+    let mut tags: Vec<TagStats> = prices_by_tag
+        .into_iter()
+        .map(|(tag, mut prices)| {
+            prices.sort_unstable();
+            let count = prices.len();
+            let mean_price = prices.iter().sum::<i64>() as f64 / count as f64;
+            let median_price = median(&prices);
+            TagStats {
+                tag,
+                count,
+                mean_price,
+                median_price,
+            }
+        })
+        .collect();
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+    let mut pair_counts: HashMap<(String, String), u32> = HashMap::new();
+    for app in apps {
+        let mut sorted_tags = app.tags.clone();
+        sorted_tags.sort();
+        sorted_tags.dedup();
+        for i in 0..sorted_tags.len() {
+            for j in (i + 1)..sorted_tags.len() {
+                *pair_counts
+                    .entry((sorted_tags[i].clone(), sorted_tags[j].clone()))
+                    .or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut top_tag_pairs: Vec<TagPairCount> = pair_counts
+        .into_iter()
+        .map(|((a, b), count)| TagPairCount { a, b, count })
+        .collect();
+    top_tag_pairs.sort_by(|x, y| y.count.cmp(&x.count));
+    top_tag_pairs.truncate(TOP_TAG_PAIRS);
+
+    Report { tags, top_tag_pairs }
+}
+
+fn median(sorted_prices: &[i64]) -> f64 {
+    if sorted_prices.is_empty() {
+        return 0.0;
+    }
+    let mid = sorted_prices.len() / 2;
+    if sorted_prices.len() % 2 == 0 {
+        (sorted_prices[mid - 1] + sorted_prices[mid]) as f64 / 2.0
+    } else {
+        sorted_prices[mid] as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_of_empty_slice_is_zero() {
+        assert_eq!(median(&[]), 0.0);
+    }
+
+    #[test]
+    fn median_of_even_and_odd_length_slices() {
+        assert_eq!(median(&[100, 200]), 150.0);
+        assert_eq!(median(&[100, 200, 900]), 200.0);
+    }
+
+    #[test]
+    fn build_report_aggregates_tag_price_stats_and_pairs() {
+        let apps = vec![
+            App::new(1, "A".to_string(), vec!["rpg".to_string(), "indie".to_string()], 1000, false, "USD".to_string()),
+            App::new(2, "B".to_string(), vec!["rpg".to_string()], 2000, false, "USD".to_string()),
+            App::new(3, "C".to_string(), vec!["indie".to_string()], 0, true, "USD".to_string()),
+        ];
+
+        let report = build_report(&apps);
+
+        let rpg = report.tags.iter().find(|t| t.tag == "rpg").unwrap();
+        assert_eq!(rpg.count, 2);
+        assert_eq!(rpg.mean_price, 1500.0);
+        assert_eq!(rpg.median_price, 1500.0);
+
+        let indie = report.tags.iter().find(|t| t.tag == "indie").unwrap();
+        assert_eq!(indie.count, 2);
+        assert_eq!(indie.mean_price, 500.0);
+
+        let pair = report
+            .top_tag_pairs
+            .iter()
+            .find(|p| (p.a == "indie" && p.b == "rpg") || (p.a == "rpg" && p.b == "indie"))
+            .unwrap();
+        assert_eq!(pair.count, 1);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::{Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::AppId;
+
+/// The longest backoff a retried id will ever wait for, regardless of
+/// how many times it has failed.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The total number of times an id may be tried (the first fetch plus
+/// its retries) before it's given up on.
+const MAX_RETRY_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Item {
+    ready_at: Instant,
+    id: AppId,
+    attempt: u32,
+}
+
+impl Ord for Item {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.ready_at.cmp(&other.ready_at).then(self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for Item {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct State {
+    queue: BinaryHeap<Reverse<Item>>,
+    next_allowed_fetch: Instant,
+    closed: bool,
+}
+
+/// A run queue that hands out at most one id per `delay` interval, and
+/// lets failed ids be re-enqueued with exponential backoff instead of
+/// being dropped.
+pub struct Scheduler {
+    delay: Duration,
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+impl Scheduler {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            state: Mutex::new(State {
+                queue: BinaryHeap::new(),
+                next_allowed_fetch: Instant::now(),
+                closed: false,
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Schedules `id` to be fetched as soon as the rate limit allows.
+    pub fn push(&self, id: AppId) {
+        self.push_at(id, 0, Instant::now());
+    }
+
+    /// Re-schedules `id` after a request for it failed, doubling the
+    /// backoff for every prior attempt up to [`MAX_RETRY_BACKOFF`].
+    ///
+    /// Returns `false` without re-enqueueing once `attempt + 1` has
+    /// reached [`MAX_RETRY_ATTEMPTS`], i.e. the id has been tried that
+    /// many times.
+    pub fn retry(&self, id: AppId, attempt: u32) -> bool {
+        if attempt + 1 >= MAX_RETRY_ATTEMPTS {
+            return false;
+        }
+        let factor = 1u32.checked_shl(attempt.min(16)).unwrap_or(u32::MAX);
+        let backoff = self
+            .delay
+            .checked_mul(factor)
+            .unwrap_or(MAX_RETRY_BACKOFF)
+            .min(MAX_RETRY_BACKOFF);
+        self.push_at(id, attempt + 1, Instant::now() + backoff);
+        true
+    }
+
+    fn push_at(&self, id: AppId, attempt: u32, ready_at: Instant) {
+        let mut state = self.state.lock().unwrap();
+        state.queue.push(Reverse(Item {
+            ready_at,
+            id,
+            attempt,
+        }));
+        drop(state);
+        self.condvar.notify_all();
+    }
+
+    /// Blocks until the next-allowed-fetch slot opens, then reserves it,
+    /// without popping anything off the queue. Use this to rate-limit a
+    /// second outgoing request for an id already handed out by `pop`.
+    pub fn throttle(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            if now >= state.next_allowed_fetch {
+                state.next_allowed_fetch = now + self.delay;
+                return;
+            }
+            let wait = state.next_allowed_fetch - now;
+            state = self.condvar.wait_timeout(state, wait).unwrap().0;
+        }
+    }
+
+    /// Signals that no more ids will be pushed.
+    pub fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        drop(state);
+        self.condvar.notify_all();
+    }
+
+    /// Blocks until an id is both due (`ready_at` has passed) and the
+    /// next-allowed-fetch slot has opened up, returning its attempt
+    /// count. Once the scheduler is closed, returns `None` as soon as no
+    /// item can be handed out right away.
+    pub fn pop(&self) -> Option<(AppId, u32)> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(Reverse(item)) = state.queue.peek() {
+                let now = Instant::now();
+                let wait_until = item.ready_at.max(state.next_allowed_fetch);
+                if now >= wait_until {
+                    let Reverse(item) = state.queue.pop().unwrap();
+                    state.next_allowed_fetch = now + self.delay;
+                    return Some((item.id, item.attempt));
+                }
+                if state.closed {
+                    return None;
+                }
+                state = self
+                    .condvar
+                    .wait_timeout(state, wait_until - now)
+                    .unwrap()
+                    .0;
+            } else if state.closed {
+                return None;
+            } else {
+                state = self
+                    .condvar
+                    .wait_timeout(state, Duration::from_millis(100))
+                    .unwrap()
+                    .0;
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(250))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_stops_once_max_attempts_reached() {
+        let scheduler = Scheduler::new(Duration::from_millis(1));
+        for attempt in 0..MAX_RETRY_ATTEMPTS - 1 {
+            assert!(scheduler.retry(1, attempt), "attempt {attempt} should retry");
+        }
+        assert!(!scheduler.retry(1, MAX_RETRY_ATTEMPTS - 1));
+    }
+
+    #[test]
+    fn pop_returns_none_for_not_yet_ready_retry_once_closed() {
+        let scheduler = Scheduler::new(Duration::from_secs(3600));
+        // A backoff this long won't be ready during the test; pop
+        // should give up on it as soon as the scheduler is closed
+        // rather than waiting it out.
+        scheduler.retry(1, 3);
+        scheduler.close();
+
+        let started = Instant::now();
+        assert_eq!(scheduler.pop(), None);
+        assert!(
+            started.elapsed() < Duration::from_secs(1),
+            "pop should give up on a not-yet-ready retry once closed, not wait it out"
+        );
+    }
+
+    #[test]
+    fn pop_returns_ready_items_in_ready_at_order() {
+        let scheduler = Scheduler::new(Duration::from_millis(1));
+        scheduler.push(1);
+        std::thread::sleep(Duration::from_millis(5));
+        scheduler.push(2);
+
+        let (first, _) = scheduler.pop().unwrap();
+        assert_eq!(first, 1);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+use tracing::info;
+
+use crate::{price, App, AppId};
+
+const APPDETAILS_URL: &str = "https://store.steampowered.com/api/appdetails";
+
+#[derive(Debug, Deserialize)]
+struct AppDetailsEntry {
+    success: bool,
+    data: Option<AppDetailsData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppDetailsData {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    is_free: bool,
+    #[serde(default)]
+    price_overview: Option<PriceOverview>,
+    #[serde(default)]
+    genres: Vec<Genre>,
+    #[serde(default)]
+    categories: Vec<Category>,
+    #[serde(default)]
+    fullgame: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceOverview {
+    #[serde(rename = "final")]
+    final_cents: i64,
+    currency: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Genre {
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Category {
+    description: String,
+}
+
+/// Fetches an app's details from Steam's storefront JSON API, requesting
+/// prices in the currency/language tied to `country` (a `cc` code like
+/// `"us"` or `"de"`).
+///
+/// Returns `Ok(None)` when the API reports `success: false` (unknown
+/// app, region-locked, etc.) or when the entry turns out to be DLC; the
+/// caller falls back to scraping the HTML store page in that case.
+pub fn fetch_appdetails(id: AppId, country: &str) -> color_eyre::Result<Option<App>> {
+    let response: HashMap<String, AppDetailsEntry> = ureq::get(APPDETAILS_URL)
+        .query("appids", &id.to_string())
+        .query("cc", country)
+        .query("l", price::language_for_country(country))
+        .call()?
+        .into_json()?;
+
+    let Some(entry) = response.get(&id.to_string()) else {
+        return Ok(None);
+    };
+
+    if !entry.success {
+        return Ok(None);
+    }
+
+    let Some(data) = &entry.data else {
+        return Ok(None);
+    };
+
+    if data.fullgame.is_some() || data.kind.eq_ignore_ascii_case("dlc") {
+        info!("Skipping DLC app {id}");
+        return Ok(None);
+    }
+
+    Ok(Some(app_from_details(id, data)))
+}
+
+/// Builds an [`App`] from an already-deserialized, non-DLC appdetails
+/// entry.
+fn app_from_details(id: AppId, data: &AppDetailsData) -> App {
+    let mut tags: Vec<String> = data
+        .genres
+        .iter()
+        .map(|genre| genre.description.clone())
+        .chain(data.categories.iter().map(|category| category.description.clone()))
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    let (price, currency) = match &data.price_overview {
+        Some(overview) if !data.is_free => (overview.final_cents, overview.currency.clone()),
+        Some(overview) => (0, overview.currency.clone()),
+        None => (0, price::DEFAULT_CURRENCY.to_string()),
+    };
+
+    App::new(
+        id,
+        data.name.clone(),
+        tags,
+        price,
+        data.is_free || price == 0,
+        currency,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(name: &str) -> AppDetailsData {
+        AppDetailsData {
+            name: name.to_string(),
+            kind: "game".to_string(),
+            is_free: false,
+            price_overview: None,
+            genres: vec![Genre {
+                description: "RPG".to_string(),
+            }],
+            categories: vec![Category {
+                description: "Single-player".to_string(),
+            }],
+            fullgame: None,
+        }
+    }
+
+    #[test]
+    fn paid_app_with_price_overview_uses_its_price_and_currency() {
+        let mut entry = data("Paid Game");
+        entry.price_overview = Some(PriceOverview {
+            final_cents: 1999,
+            currency: "USD".to_string(),
+        });
+
+        let app = app_from_details(1, &entry);
+
+        assert_eq!(app.price, 1999);
+        assert_eq!(app.currency, "USD");
+        assert!(!app.is_free);
+        assert_eq!(app.tags, vec!["RPG", "Single-player"]);
+    }
+
+    #[test]
+    fn free_app_with_price_overview_is_zero_cost() {
+        let mut entry = data("Free Game");
+        entry.is_free = true;
+        entry.price_overview = Some(PriceOverview {
+            final_cents: 0,
+            currency: "EUR".to_string(),
+        });
+
+        let app = app_from_details(2, &entry);
+
+        assert_eq!(app.price, 0);
+        assert_eq!(app.currency, "EUR");
+        assert!(app.is_free);
+    }
+
+    #[test]
+    fn missing_price_overview_falls_back_to_default_currency() {
+        let entry = data("No Price Entry");
+
+        let app = app_from_details(3, &entry);
+
+        assert_eq!(app.price, 0);
+        assert_eq!(app.currency, price::DEFAULT_CURRENCY);
+        assert!(app.is_free);
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::AppId;
+
+const TYPE_MAP: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT32: u8 = 0x02;
+const TYPE_FLOAT32: u8 = 0x03;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+
+/// The bits of a `common` entry in an app's key/value tree that are
+/// useful as crawl seeds or to fill in metadata we'd otherwise have to
+/// fetch over the network.
+#[derive(Debug, Clone, Default)]
+pub struct AppInfoEntry {
+    pub app_id: AppId,
+    pub name: Option<String>,
+    pub kind: Option<String>,
+    /// Always empty for now: `store_tags` is keyed by numeric Steam tag
+    /// IDs we have no name table for.
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Map(HashMap<String, Node>),
+    Str(String),
+    Int(i64),
+}
+
+impl Node {
+    fn as_string(&self) -> Option<String> {
+        match self {
+            Node::Str(s) => Some(s.clone()),
+            Node::Int(i) => Some(i.to_string()),
+            Node::Map(_) => None,
+        }
+    }
+
+    fn as_map(&self) -> Option<&HashMap<String, Node>> {
+        match self {
+            Node::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn eof(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn bytes(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.pos + n > self.buf.len() {
+            return Err(unexpected_eof());
+        }
+        let slice = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn cstr(&mut self) -> io::Result<String> {
+        let start = self.pos;
+        while self.pos < self.buf.len() && self.buf[self.pos] != 0 {
+            self.pos += 1;
+        }
+        if self.pos >= self.buf.len() {
+            return Err(unexpected_eof());
+        }
+        let s = String::from_utf8_lossy(&self.buf[start..self.pos]).into_owned();
+        self.pos += 1;
+        Ok(s)
+    }
+}
+
+fn unexpected_eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated appinfo.vdf")
+}
+
+fn read_map(reader: &mut Reader) -> io::Result<HashMap<String, Node>> {
+    let mut map = HashMap::new();
+    loop {
+        let tag = reader.u8()?;
+        if tag == TYPE_END {
+            break;
+        }
+        let key = reader.cstr()?;
+        let value = match tag {
+            TYPE_MAP => Some(Node::Map(read_map(reader)?)),
+            TYPE_STRING => Some(Node::Str(reader.cstr()?)),
+            TYPE_INT32 | TYPE_FLOAT32 => Some(Node::Int(reader.u32()? as i64)),
+            TYPE_UINT64 => Some(Node::Int(reader.u64()? as i64)),
+            _ => {
+                // Fields we don't otherwise care about (review scores,
+                // pointers, colors, ...) are all fixed-width 4-byte
+                // values in practice; skip them rather than failing the
+                // whole entry over a tag we don't model.
+                reader.u32()?;
+                None
+            }
+        };
+        if let Some(value) = value {
+            map.insert(key, value);
+        }
+    }
+    Ok(map)
+}
+
+/// Parses Steam's binary `appinfo.vdf` cache into a list of entries,
+/// following the on-disk layout: a file magic and universe `u32`, then
+/// repeated entries of `app_id`, `info_state`, `last_updated`,
+/// `pics_token`, a 20-byte text-VDF SHA1, `change_number`, and a nested
+/// binary key/value tree whose maps end in an end-of-map type byte.
+/// Only `common`/`name` and `common`/`type` are taken from the tree;
+/// `tags` is always left empty (see [`AppInfoEntry::tags`]).
+pub fn parse_appinfo(path: impl AsRef<Path>) -> io::Result<Vec<AppInfoEntry>> {
+    let data = fs::read(path)?;
+    let mut reader = Reader::new(&data);
+
+    let _magic = reader.u32()?;
+    let _universe = reader.u32()?;
+
+    let mut entries = Vec::new();
+    while !reader.eof() {
+        let app_id = reader.u32()?;
+        if app_id == 0 {
+            break;
+        }
+        let _info_state = reader.u32()?;
+        let _last_updated = reader.u32()?;
+        let _pics_token = reader.u64()?;
+        let _text_vdf_sha1 = reader.bytes(20)?;
+        let _change_number = reader.u32()?;
+        let tree = read_map(&mut reader)?;
+
+        let common = tree.get("common").and_then(Node::as_map);
+        let name = common.and_then(|c| c.get("name")).and_then(Node::as_string);
+        let kind = common.and_then(|c| c.get("type")).and_then(Node::as_string);
+        // `common/store_tags` maps to numeric Steam tag IDs, not names,
+        // and we have no ID-to-name table to resolve them against, so
+        // leave `tags` empty rather than enriching `App.tags` with bare
+        // numbers that don't match anything from the storefront API.
+        let tags = Vec::new();
+
+        entries.push(AppInfoEntry {
+            app_id,
+            name,
+            kind,
+            tags,
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_cstr(buf: &mut Vec<u8>, s: &str) {
+        buf.extend_from_slice(s.as_bytes());
+        buf.push(0);
+    }
+
+    /// Builds a minimal single-entry `appinfo.vdf` buffer whose
+    /// `common` map is filled in by `write_common`.
+    fn build_appinfo(write_common: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&1u32.to_le_bytes()); // magic
+        buf.extend_from_slice(&1u32.to_le_bytes()); // universe
+
+        buf.extend_from_slice(&42u32.to_le_bytes()); // app_id
+        buf.extend_from_slice(&0u32.to_le_bytes()); // info_state
+        buf.extend_from_slice(&0u32.to_le_bytes()); // last_updated
+        buf.extend_from_slice(&0u64.to_le_bytes()); // pics_token
+        buf.extend_from_slice(&[0u8; 20]); // text_vdf_sha1
+        buf.extend_from_slice(&0u32.to_le_bytes()); // change_number
+
+        buf.push(TYPE_MAP);
+        push_cstr(&mut buf, "common");
+        write_common(&mut buf);
+        buf.push(TYPE_END); // end of "common"
+        buf.push(TYPE_END); // end of top-level tree
+
+        buf.extend_from_slice(&0u32.to_le_bytes()); // terminating app_id
+        buf
+    }
+
+    fn parse_buf(buf: &[u8]) -> Vec<AppInfoEntry> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "steam_crawler_test_appinfo_{}_{}.vdf",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, buf).unwrap();
+        let entries = parse_appinfo(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        entries
+    }
+
+    #[test]
+    fn parse_appinfo_skips_unrecognized_node_types() {
+        let buf = build_appinfo(|buf| {
+            buf.push(TYPE_STRING);
+            push_cstr(buf, "name");
+            push_cstr(buf, "Test Game");
+            buf.push(TYPE_STRING);
+            push_cstr(buf, "type");
+            push_cstr(buf, "Game");
+            // A node type this parser doesn't model (e.g. Steam's
+            // "Color" tag, 0x06) should be skipped rather than failing
+            // the parse.
+            buf.push(0x06);
+            push_cstr(buf, "score");
+            buf.extend_from_slice(&0u32.to_le_bytes());
+        });
+
+        let entries = parse_buf(&buf);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].app_id, 42);
+        assert_eq!(entries[0].name.as_deref(), Some("Test Game"));
+        assert_eq!(entries[0].kind.as_deref(), Some("Game"));
+    }
+
+    #[test]
+    fn store_tags_numeric_ids_are_not_surfaced_as_tags() {
+        let buf = build_appinfo(|buf| {
+            buf.push(TYPE_MAP);
+            push_cstr(buf, "store_tags");
+            buf.push(TYPE_INT32);
+            push_cstr(buf, "0");
+            buf.extend_from_slice(&113u32.to_le_bytes());
+            buf.push(TYPE_END); // end of "store_tags"
+        });
+
+        let entries = parse_buf(&buf);
+
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].tags.is_empty());
+    }
+}